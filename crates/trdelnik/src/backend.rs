@@ -0,0 +1,282 @@
+use async_trait::async_trait;
+use fehler::throws;
+use thiserror::Error;
+use solana_sdk::{
+    loader_instruction,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+use solana_banks_client::{BanksClient, BanksClientError};
+use solana_program_test::ProgramTest;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    BanksClientError(#[from] BanksClientError),
+    #[error("{0:?}")]
+    ClientError(#[from] solana_client::client_error::ClientError),
+}
+
+/// Abstraction over the two ways a `#[trdelnik_test]` fixture can talk to a
+/// Solana runtime: a real `solana-test-validator` process over RPC, or an
+/// in-process `BanksClient` connected directly to a `Bank`.
+///
+/// _Note_: `Client`/`Fixture` aren't generic over this trait yet — that
+/// requires touching `Client`'s own module, which isn't part of this
+/// change. `BanksBackend`/`ValidatorBackend` are usable directly by a test
+/// that doesn't go through `Client` in the meantime; see the `tests` module
+/// below for `BanksBackend` exercised that way.
+#[async_trait]
+pub trait TestBackend {
+    #[throws(Error)]
+    async fn deploy(&mut self, payer: &Keypair, program: Keypair, program_data: Vec<u8>);
+
+    #[throws(Error)]
+    async fn create_account_with_data(&mut self, payer: &Keypair, account: &Keypair, owner: Pubkey, data: Vec<u8>);
+
+    #[throws(Error)]
+    async fn account_data(&mut self, account: Pubkey) -> Vec<u8>;
+
+    #[throws(Error)]
+    async fn airdrop(&mut self, account: Pubkey, lamports: u64);
+
+    #[throws(Error)]
+    async fn send_transaction(&mut self, transaction: Transaction);
+}
+
+fn clone_keypair(keypair: &Keypair) -> Keypair {
+    Keypair::from_bytes(&keypair.to_bytes()).unwrap()
+}
+
+/// In-process backend running a `BanksServer`/`BanksClient` pair over an
+/// in-memory channel instead of an RPC socket on `:8899`. No ledger
+/// directory, no port binding, millisecond-scale startup.
+pub struct BanksBackend {
+    context: solana_program_test::ProgramTestContext,
+}
+
+impl BanksBackend {
+    #[throws(Error)]
+    pub async fn new(program_test: ProgramTest) -> Self {
+        Self {
+            context: program_test.start_with_context().await,
+        }
+    }
+
+    /// The context's genesis payer, pre-funded by `ProgramTest` and usable
+    /// as a fee payer/lamport source without a real airdrop.
+    pub fn payer(&self) -> &Keypair {
+        &self.context.payer
+    }
+
+    fn client(&mut self) -> &mut BanksClient {
+        &mut self.context.banks_client
+    }
+}
+
+#[async_trait]
+impl TestBackend for BanksBackend {
+    #[throws(Error)]
+    async fn deploy(&mut self, _payer: &Keypair, _program: Keypair, _program_data: Vec<u8>) {
+        // Programs are loaded into the `Bank` up front via `ProgramTest`,
+        // so deploying after start-up is a no-op on this backend.
+    }
+
+    #[throws(Error)]
+    async fn create_account_with_data(&mut self, payer: &Keypair, account: &Keypair, owner: Pubkey, data: Vec<u8>) {
+        let rent = self.client().get_rent().await?;
+        let lamports = rent.minimum_balance(data.len());
+        let blockhash = self.client().get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[solana_sdk::system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                lamports,
+                data.len() as u64,
+                &owner,
+            )],
+            Some(&payer.pubkey()),
+            &[payer, account],
+            blockhash,
+        );
+        self.client().process_transaction(transaction).await?;
+        // `create_account` only allocates zeroed space; write the bytes
+        // straight into the bank's copy of the account.
+        self.context.set_account(
+            &account.pubkey(),
+            &solana_sdk::account::AccountSharedData::from(solana_sdk::account::Account {
+                lamports,
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            }),
+        );
+    }
+
+    #[throws(Error)]
+    async fn account_data(&mut self, account: Pubkey) -> Vec<u8> {
+        self.client()
+            .get_account(account)
+            .await?
+            .map(|account| account.data)
+            .unwrap_or_default()
+    }
+
+    #[throws(Error)]
+    async fn airdrop(&mut self, account: Pubkey, lamports: u64) {
+        // Banks has no faucet RPC; "airdropping" just transfers from the
+        // context's pre-funded genesis payer instead of a brand-new,
+        // zero-balance keypair.
+        let payer = clone_keypair(&self.context.payer);
+        let blockhash = self.client().get_latest_blockhash().await?;
+        self.client()
+            .process_transaction(solana_sdk::system_transaction::transfer(
+                &payer,
+                &account,
+                lamports,
+                blockhash,
+            ))
+            .await?;
+    }
+
+    #[throws(Error)]
+    async fn send_transaction(&mut self, transaction: Transaction) {
+        self.client().process_transaction(transaction).await?;
+    }
+}
+
+/// Backend talking to an externally spawned `solana-test-validator` process
+/// over RPC, i.e. what `Commander::start_localnet` has always produced.
+pub struct ValidatorBackend {
+    client: solana_client::rpc_client::RpcClient,
+}
+
+impl ValidatorBackend {
+    pub fn new(client: solana_client::rpc_client::RpcClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl TestBackend for ValidatorBackend {
+    #[throws(Error)]
+    async fn deploy(&mut self, payer: &Keypair, program: Keypair, program_data: Vec<u8>) {
+        // The legacy BPF loader has no single-transaction deploy: create
+        // the program account, then chunk the ELF across `write`
+        // instructions (a whole program doesn't fit under the ~1232-byte
+        // transaction size limit), then `finalize` it.
+        const CHUNK_SIZE: usize = 900;
+
+        let rent = self.client.get_minimum_balance_for_rent_exemption(program_data.len())?;
+        let blockhash = self.client.get_latest_blockhash()?;
+        let create_account_ix = solana_sdk::system_instruction::create_account(
+            &payer.pubkey(),
+            &program.pubkey(),
+            rent,
+            program_data.len() as u64,
+            &solana_sdk::bpf_loader::id(),
+        );
+        let create_account_tx = Transaction::new_signed_with_payer(
+            &[create_account_ix],
+            Some(&payer.pubkey()),
+            &[payer, &program],
+            blockhash,
+        );
+        self.client.send_and_confirm_transaction(&create_account_tx)?;
+
+        for (offset, chunk) in program_data.chunks(CHUNK_SIZE).enumerate() {
+            let blockhash = self.client.get_latest_blockhash()?;
+            let write_ix = loader_instruction::write(
+                &program.pubkey(),
+                &solana_sdk::bpf_loader::id(),
+                (offset * CHUNK_SIZE) as u32,
+                chunk.to_vec(),
+            );
+            let write_tx = Transaction::new_signed_with_payer(
+                &[write_ix],
+                Some(&payer.pubkey()),
+                &[payer, &program],
+                blockhash,
+            );
+            self.client.send_and_confirm_transaction(&write_tx)?;
+        }
+
+        let blockhash = self.client.get_latest_blockhash()?;
+        let finalize_ix = loader_instruction::finalize(&program.pubkey(), &solana_sdk::bpf_loader::id());
+        let finalize_tx = Transaction::new_signed_with_payer(
+            &[finalize_ix],
+            Some(&payer.pubkey()),
+            &[payer, &program],
+            blockhash,
+        );
+        self.client.send_and_confirm_transaction(&finalize_tx)?;
+    }
+
+    #[throws(Error)]
+    async fn create_account_with_data(&mut self, payer: &Keypair, account: &Keypair, owner: Pubkey, data: Vec<u8>) {
+        let rent = self.client.get_minimum_balance_for_rent_exemption(data.len())?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[solana_sdk::system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent,
+                data.len() as u64,
+                &owner,
+            )],
+            Some(&payer.pubkey()),
+            &[payer, account],
+            self.client.get_latest_blockhash()?,
+        );
+        self.client.send_and_confirm_transaction(&transaction)?;
+    }
+
+    #[throws(Error)]
+    async fn account_data(&mut self, account: Pubkey) -> Vec<u8> {
+        self.client.get_account_data(&account)?
+    }
+
+    #[throws(Error)]
+    async fn airdrop(&mut self, account: Pubkey, lamports: u64) {
+        let signature = self.client.request_airdrop(&account, lamports)?;
+        self.client.confirm_transaction(&signature)?;
+    }
+
+    #[throws(Error)]
+    async fn send_transaction(&mut self, transaction: Transaction) {
+        self.client.send_and_confirm_transaction(&transaction)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn create_account_with_data_is_readable_back() {
+        let mut backend = BanksBackend::new(ProgramTest::default()).await.unwrap();
+        let payer = clone_keypair(backend.payer());
+        let account = Keypair::new();
+        let data = vec![1, 2, 3, 4, 5];
+
+        backend
+            .create_account_with_data(&payer, &account, Pubkey::new_unique(), data.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(backend.account_data(account.pubkey()).await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn airdrop_credits_the_destination_account() {
+        let mut backend = BanksBackend::new(ProgramTest::default()).await.unwrap();
+        let destination = Pubkey::new_unique();
+
+        backend.airdrop(destination, 10_000_000).await.unwrap();
+
+        let balance = backend.context.banks_client.get_balance(destination).await.unwrap();
+        assert_eq!(balance, 10_000_000);
+    }
+}
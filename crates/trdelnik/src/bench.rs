@@ -0,0 +1,159 @@
+use fehler::throws;
+use thiserror::Error;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::Transaction,
+};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    ClientError(#[from] solana_client::client_error::ClientError),
+}
+
+/// Parameters for a [`Commander::bench`](crate::commander::Commander::bench) run.
+pub struct BenchConfig {
+    /// Number of threads concurrently firing transaction batches.
+    pub threads: usize,
+    /// Transactions fired per batch per thread.
+    pub batch_size: usize,
+    /// How long to keep firing batches for.
+    pub duration: Duration,
+    /// Maximum number of unconfirmed signatures kept in flight at once,
+    /// mirroring accounts-cluster-bench's bounded queue.
+    pub max_in_flight: usize,
+    pub commitment: CommitmentConfig,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            threads: 4,
+            batch_size: 50,
+            duration: Duration::from_secs(10),
+            max_in_flight: 5_000,
+            commitment: CommitmentConfig::confirmed(),
+        }
+    }
+}
+
+/// Throughput and latency summary of a [`Commander::bench`](crate::commander::Commander::bench) run.
+#[derive(Debug, Default)]
+pub struct BenchReport {
+    pub transactions_sent: u64,
+    pub transactions_confirmed: u64,
+    pub transactions_failed: u64,
+    pub duration: Duration,
+    /// Confirmation latency percentiles, in milliseconds: (p50, p90, p99).
+    pub latency_percentiles_ms: (u64, u64, u64),
+}
+
+impl BenchReport {
+    pub fn tps(&self) -> f64 {
+        self.transactions_confirmed as f64 / self.duration.as_secs_f64()
+    }
+}
+
+/// Keeps a bounded set of in-flight signatures and polls for confirmation,
+/// modeled on Solana's accounts-cluster-bench `TransactionExecutor`.
+pub struct TransactionExecutor {
+    rpc_client: solana_client::rpc_client::RpcClient,
+    in_flight: VecDeque<(Signature, Instant)>,
+    max_in_flight: usize,
+    latencies_ms: Vec<u64>,
+    confirmed: u64,
+    failed: u64,
+}
+
+impl TransactionExecutor {
+    pub fn new(rpc_client: solana_client::rpc_client::RpcClient, max_in_flight: usize) -> Self {
+        Self {
+            rpc_client,
+            in_flight: VecDeque::new(),
+            max_in_flight,
+            latencies_ms: Vec::new(),
+            confirmed: 0,
+            failed: 0,
+        }
+    }
+
+    #[throws(Error)]
+    pub fn latest_blockhash(&self) -> solana_sdk::hash::Hash {
+        self.rpc_client.get_latest_blockhash()?
+    }
+
+    /// Blocks until at least one slot is free, polling the oldest
+    /// in-flight signatures first. Unlike [`TransactionExecutor::drain_confirmed`],
+    /// this keeps retrying rather than giving up at the first still-pending
+    /// signature, so the queue can never grow past `max_in_flight`.
+    #[throws(Error)]
+    fn wait_for_capacity(&mut self) {
+        while self.in_flight.len() >= self.max_in_flight {
+            self.drain_confirmed()?;
+            if self.in_flight.len() >= self.max_in_flight {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+
+    /// Counts a transaction that never got a signature at all (blockhash
+    /// fetch or `send_transaction` itself failed), so callers that give up
+    /// before `send` returns `Ok` don't undercount failures.
+    pub fn record_failed(&mut self) {
+        self.failed += 1;
+    }
+
+    #[throws(Error)]
+    pub fn send(&mut self, transaction: &Transaction) {
+        self.wait_for_capacity()?;
+        let signature = self.rpc_client.send_transaction(transaction)?;
+        self.in_flight.push_back((signature, Instant::now()));
+    }
+
+    #[throws(Error)]
+    pub fn drain_confirmed(&mut self) {
+        while let Some((signature, sent_at)) = self.in_flight.pop_front() {
+            match self.rpc_client.confirm_transaction(&signature) {
+                Ok(true) => {
+                    self.confirmed += 1;
+                    self.latencies_ms.push(sent_at.elapsed().as_millis() as u64);
+                }
+                Ok(false) => {
+                    self.in_flight.push_front((signature, sent_at));
+                    break;
+                }
+                Err(_) => self.failed += 1,
+            }
+        }
+    }
+
+    /// Drains whatever is still in flight, then returns this thread's
+    /// confirmed/failed counts and raw confirmation latencies. Percentiles
+    /// aren't computed here because [`Commander::bench`](crate::commander::Commander::bench)
+    /// needs to merge latencies across every thread before taking
+    /// percentiles of the combined run, not average per-thread percentiles.
+    pub fn into_parts(mut self) -> (u64, u64, Vec<u64>) {
+        let _ = self.drain_confirmed();
+        (self.confirmed, self.failed + self.in_flight.len() as u64, self.latencies_ms)
+    }
+}
+
+/// Computes the (p50, p90, p99) latency percentiles, in milliseconds, of an
+/// already-sorted-or-not set of confirmation latencies.
+pub fn latency_percentiles_ms(latencies_ms: &mut Vec<u64>) -> (u64, u64, u64) {
+    latencies_ms.sort_unstable();
+    let percentile = |p: f64| -> u64 {
+        if latencies_ms.is_empty() {
+            0
+        } else {
+            let index = ((latencies_ms.len() as f64 - 1.0) * p) as usize;
+            latencies_ms[index]
+        }
+    };
+    (percentile(0.5), percentile(0.9), percentile(0.99))
+}
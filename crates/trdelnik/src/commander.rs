@@ -5,7 +5,12 @@ use std::{borrow::Cow, io, string::FromUtf8Error, process::Stdio, path::Path};
 use solana_sdk::signer::keypair::Keypair;
 use cargo_metadata::MetadataCommand;
 use futures::future::try_join_all;
-use crate::{idl::{self, Idl}, Client, program_client_generator};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use std::time::{Duration, Instant};
+use solana_sdk::{instruction::Instruction, signer::Signer, transaction::Transaction};
+use crate::{backend::BanksBackend, bench::{latency_percentiles_ms, BenchConfig, BenchReport, TransactionExecutor}, genesis::GenesisBuilder, idl::{self, Idl}, logs::LogCapture, Client, program_client_generator};
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -23,10 +28,24 @@ pub enum Error {
     ReadProgramCodeFailed(String),
     #[error("{0:?}")]
     IdlError(#[from] idl::Error),
+    #[error("{0:?}")]
+    BackendError(#[from] crate::backend::Error),
+    #[error("{0:?}")]
+    GenesisError(#[from] crate::genesis::Error),
+    #[error("{0:?}")]
+    LogsError(#[from] crate::logs::Error),
+    #[error("{0:?}")]
+    PubsubClientError(#[from] solana_client::pubsub_client::PubsubClientError),
+    #[error("{0:?}")]
+    BenchError(#[from] crate::bench::Error),
+    #[error("{0:?}")]
+    JoinError(#[from] tokio::task::JoinError),
 }
 
 pub struct LocalnetHandle {
     solana_test_validator_process: Child,
+    log_capture: Arc<Mutex<LogCapture>>,
+    log_subscription: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl LocalnetHandle {
@@ -36,13 +55,45 @@ impl LocalnetHandle {
         // @TODO Why does `await` wait indefinitely when `stop` is called by Jupyter Kernel?
         // self.solana_test_validator_process.kill().await?;
         self.solana_test_validator_process.start_kill()?;
+        if let Some(log_subscription) = self.log_subscription.take() {
+            log_subscription.abort();
+        }
 
         if Client::new(Keypair::new()).is_localnet_running(false).await {
             Err(Error::LocalnetIsStillRunning)?
         }
+        self.log_capture.lock().await.persist(".anchor/program-logs").await?;
         fs::remove_dir_all("test-ledger").await?;
         println!("localnet stopped and its ledger deleted");
     }
+
+    /// Logs emitted by `program_id` across every transaction sent so far.
+    pub async fn logs(&self, program_id: solana_sdk::pubkey::Pubkey) -> Vec<String> {
+        self.log_capture.lock().await.logs(program_id)
+    }
+
+    /// Asserts that `program_id` emitted a log line containing `substring`.
+    pub async fn assert_log_contains(&self, program_id: solana_sdk::pubkey::Pubkey, substring: &str) {
+        self.log_capture.lock().await.assert_log_contains(program_id, substring);
+    }
+
+    #[throws]
+    async fn subscribe_logs(&mut self) {
+        let log_capture = self.log_capture.clone();
+        let (pubsub_client, mut log_stream) = solana_client::nonblocking::pubsub_client::PubsubClient::logs_subscribe(
+            "ws://127.0.0.1:8900",
+            RpcTransactionLogsFilter::All,
+            RpcTransactionLogsConfig { commitment: None },
+        ).await?;
+
+        self.log_subscription = Some(tokio::spawn(async move {
+            use futures::StreamExt;
+            let _pubsub_client = pubsub_client;
+            while let Some(log) = log_stream.next().await {
+                log_capture.lock().await.ingest(&log.value.logs);
+            }
+        }));
+    }
 }
 
 pub struct Commander {
@@ -138,18 +189,127 @@ impl Commander {
 
     #[throws]
     pub async fn start_localnet(&self) -> LocalnetHandle {
-        let process = Command::new("solana-test-validator")
+        self.start_localnet_with_genesis(None).await?
+    }
+
+    /// Like [`Commander::start_localnet`], but preloads the validator's
+    /// genesis with the programs and accounts collected by `genesis`
+    /// instead of deploying/airdropping them after the validator is up.
+    #[throws]
+    pub async fn start_localnet_with_genesis(&self, genesis: Option<&GenesisBuilder>) -> LocalnetHandle {
+        let mut command = Command::new("solana-test-validator");
+        command
             .arg("-C")
             .arg([&self.root, "config.yml"].concat())
             .arg("-r")
-            .arg("-q")
-            .spawn()?;
+            .arg("-q");
+
+        if let Some(genesis) = genesis {
+            let genesis_args = genesis.build([&self.root, "test-genesis"].concat()).await?;
+            command.args(genesis_args);
+        }
+
+        let process = command.spawn()?;
         if !Client::new(Keypair::new()).is_localnet_running(true).await {
             Err(Error::LocalnetIsNotRunning)?
         }
         println!("localnet started");
-        LocalnetHandle {
+        let mut handle = LocalnetHandle {
             solana_test_validator_process: process,
+            log_capture: Arc::new(Mutex::new(LogCapture::new())),
+            log_subscription: None,
+        };
+        handle.subscribe_logs().await?;
+        handle
+    }
+
+    /// Starts an in-process `BanksClient` backend instead of spawning
+    /// `solana-test-validator`. No ledger directory, no port binding, and
+    /// transactions land in milliseconds, so it's the preferred backend
+    /// for `#[trdelnik_test]` fixtures that don't need a real RPC endpoint.
+    #[throws]
+    pub async fn start_banks_client(&self, program_test: solana_program_test::ProgramTest) -> BanksBackend {
+        BanksBackend::new(program_test).await?
+    }
+
+    /// Stresses a program's instructions against the running localnet, not
+    /// just their correctness: fires `batch_size` transactions per thread
+    /// per batch using `instruction_factory`, keeps a bounded set of
+    /// in-flight signatures and polls for confirmation, then reports TPS,
+    /// confirmation latency percentiles and failure counts. Modeled on
+    /// Solana's accounts-cluster-bench.
+    #[throws]
+    pub async fn bench(
+        &self,
+        payer: Keypair,
+        instruction_factory: impl Fn(usize) -> Instruction + Send + Sync + 'static,
+        config: BenchConfig,
+    ) -> BenchReport {
+        let instruction_factory = std::sync::Arc::new(instruction_factory);
+        let started_at = Instant::now();
+        let transactions_sent = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+
+        let handles = (0..config.threads).map(|thread_id| {
+            let instruction_factory = instruction_factory.clone();
+            let transactions_sent = transactions_sent.clone();
+            let payer = Keypair::from_bytes(&payer.to_bytes()).unwrap();
+            let rpc_client = solana_client::rpc_client::RpcClient::new_with_commitment(
+                "http://127.0.0.1:8899".to_string(),
+                config.commitment,
+            );
+            let batch_size = config.batch_size;
+            let max_in_flight = config.max_in_flight;
+            let duration = config.duration;
+
+            let threads = config.threads;
+
+            tokio::task::spawn_blocking(move || {
+                let mut executor = TransactionExecutor::new(rpc_client, max_in_flight);
+                // Strided indices (`thread_id`, `thread_id + threads`, ...) so no
+                // two threads ever derive the same index, regardless of how many
+                // batches a thread gets through before `duration` elapses.
+                let mut index = thread_id;
+                while started_at.elapsed() < duration {
+                    for _ in 0..batch_size {
+                        let blockhash = match executor.latest_blockhash() {
+                            Ok(blockhash) => blockhash,
+                            Err(_) => {
+                                executor.record_failed();
+                                continue;
+                            }
+                        };
+                        let transaction = Transaction::new_signed_with_payer(
+                            &[instruction_factory(index)],
+                            Some(&payer.pubkey()),
+                            &[&payer],
+                            blockhash,
+                        );
+                        match executor.send(&transaction) {
+                            Ok(()) => {
+                                transactions_sent.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            }
+                            Err(_) => executor.record_failed(),
+                        }
+                        index += threads;
+                    }
+                }
+                executor
+            })
+        });
+
+        let executors = try_join_all(handles).await?;
+        let transactions_sent = transactions_sent.load(std::sync::atomic::Ordering::Relaxed);
+        let mut report = BenchReport::default();
+        report.duration = started_at.elapsed();
+        let mut all_latencies_ms = Vec::new();
+        for executor in executors {
+            let (confirmed, failed, mut latencies_ms) = executor.into_parts();
+            report.transactions_confirmed += confirmed;
+            report.transactions_failed += failed;
+            all_latencies_ms.append(&mut latencies_ms);
         }
+        report.transactions_sent = transactions_sent;
+        report.latency_percentiles_ms = latency_percentiles_ms(&mut all_latencies_ms);
+        report
     }
 }
@@ -0,0 +1,99 @@
+use fehler::throws;
+use thiserror::Error;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::path::PathBuf;
+use tokio::fs;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    IoError(#[from] std::io::Error),
+    #[error("{0:?}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// A single `--account <addr> <path.json>` entry: lamports, owner and raw
+/// data pre-seeded into the validator's genesis ledger.
+#[derive(Serialize)]
+struct AccountFile {
+    pubkey: String,
+    account: AccountFileData,
+}
+
+#[derive(Serialize)]
+struct AccountFileData {
+    lamports: u64,
+    owner: String,
+    data: [String; 2],
+    executable: bool,
+    #[serde(rename = "rentEpoch")]
+    rent_epoch: u64,
+}
+
+/// Collects programs and pre-seeded accounts a fixture wants present the
+/// moment `solana-test-validator` boots, so there's no post-launch
+/// airdrop/deploy round-trip. Mirrors the `--bpf-program`/`--account` flags
+/// the Anchor CLI passes when it embeds workspace programs into genesis.
+#[derive(Default)]
+pub struct GenesisBuilder {
+    programs: Vec<(Pubkey, Vec<u8>)>,
+    accounts: Vec<(Pubkey, u64, Pubkey, Vec<u8>)>,
+}
+
+impl GenesisBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a program binary to be rendered as `--bpf-program <addr> <path.so>`.
+    pub fn add_program(&mut self, address: Pubkey, program_data: Vec<u8>) -> &mut Self {
+        self.programs.push((address, program_data));
+        self
+    }
+
+    /// Registers a pre-seeded account to be rendered as `--account <addr> <path.json>`.
+    pub fn add_account(&mut self, address: Pubkey, lamports: u64, owner: Pubkey, data: Vec<u8>) -> &mut Self {
+        self.accounts.push((address, lamports, owner, data));
+        self
+    }
+
+    /// Writes every registered program and account to `genesis_dir` and
+    /// returns the `--bpf-program`/`--account` arguments `start_localnet`
+    /// should pass to `solana-test-validator`.
+    #[throws]
+    pub async fn build(&self, genesis_dir: impl Into<PathBuf>) -> Vec<String> {
+        let genesis_dir = genesis_dir.into();
+        fs::create_dir_all(&genesis_dir).await?;
+
+        let mut args = Vec::with_capacity((self.programs.len() + self.accounts.len()) * 3);
+
+        for (address, program_data) in &self.programs {
+            let path = genesis_dir.join(format!("{}.so", address));
+            fs::write(&path, program_data).await?;
+            args.push("--bpf-program".to_string());
+            args.push(address.to_string());
+            args.push(path.to_string_lossy().into_owned());
+        }
+
+        for (address, lamports, owner, data) in &self.accounts {
+            let path = genesis_dir.join(format!("{}.json", address));
+            let account_file = AccountFile {
+                pubkey: address.to_string(),
+                account: AccountFileData {
+                    lamports: *lamports,
+                    owner: owner.to_string(),
+                    data: [base64::encode(data), "base64".to_string()],
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            };
+            fs::write(&path, serde_json::to_vec(&account_file)?).await?;
+            args.push("--account".to_string());
+            args.push(address.to_string());
+            args.push(path.to_string_lossy().into_owned());
+        }
+
+        args
+    }
+}
@@ -0,0 +1,101 @@
+use fehler::throws;
+use thiserror::Error;
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashMap, path::Path};
+use tokio::fs;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Demultiplexes a transaction's `logMessages` by the program id whose
+/// `Program <id> invoke/success/failed` frame the lines fall between, so a
+/// test can assert on what a specific program logged instead of grepping
+/// the whole transaction result.
+///
+/// Mirrors the Anchor pattern of streaming program logs to
+/// `.anchor/program-logs` during tests, but keeps the buffers in memory
+/// until [`LogCapture::persist`] is called (usually from
+/// `LocalnetHandle::stop`).
+#[derive(Default)]
+pub struct LogCapture {
+    buffers: HashMap<Pubkey, Vec<String>>,
+}
+
+impl LogCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the `logMessages` of one confirmed transaction's metadata
+    /// into the per-program buffers. Nested CPIs push/pop a program stack
+    /// instead of tracking a single "current" program, so a line logged by
+    /// the outer program after an inner `invoke`/`success` returns is still
+    /// attributed to the outer program instead of being dropped.
+    pub fn ingest(&mut self, log_messages: &[String]) {
+        let mut stack: Vec<Pubkey> = Vec::new();
+        for line in log_messages {
+            if let Some(invoke_id) = parse_invoke(line) {
+                stack.push(invoke_id);
+            }
+            if let Some(program_id) = stack.last() {
+                self.buffers.entry(*program_id).or_default().push(line.clone());
+            }
+            if parse_program_return(line).is_some() {
+                stack.pop();
+            }
+        }
+    }
+
+    /// Logs emitted by `program_id` across every transaction ingested so far.
+    pub fn logs(&self, program_id: Pubkey) -> Vec<String> {
+        self.buffers.get(&program_id).cloned().unwrap_or_default()
+    }
+
+    /// Asserts that at least one log line emitted by `program_id` contains
+    /// `substring`, turning `msg!(...)` output into a first-class assertion
+    /// surface for security-regression tests.
+    pub fn assert_log_contains(&self, program_id: Pubkey, substring: &str) {
+        let found = self
+            .logs(program_id)
+            .iter()
+            .any(|line| line.contains(substring));
+        assert!(
+            found,
+            "expected program {} to log a line containing {:?}, got: {:?}",
+            program_id,
+            substring,
+            self.logs(program_id)
+        );
+    }
+
+    /// Writes every program's buffer to `<dir>/<program_id>.log` so CI can
+    /// archive them, the same way Anchor persists `.anchor/program-logs`.
+    #[throws(Error)]
+    pub async fn persist(&self, dir: impl AsRef<Path>) {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).await?;
+        for (program_id, lines) in &self.buffers {
+            let path = dir.join(format!("{}.log", program_id));
+            fs::write(path, lines.join("\n")).await?;
+        }
+    }
+}
+
+fn parse_invoke(line: &str) -> Option<Pubkey> {
+    let rest = line.strip_prefix("Program ")?;
+    let (id, rest) = rest.split_once(' ')?;
+    if rest.starts_with("invoke") {
+        id.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn parse_program_return(line: &str) -> Option<()> {
+    let rest = line.strip_prefix("Program ")?;
+    let (_, rest) = rest.split_once(' ')?;
+    (rest.starts_with("success") || rest.starts_with("failed")).then(|| ())
+}
@@ -0,0 +1,222 @@
+use heck::{CamelCase, ShoutySnakeCase, SnakeCase};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use crate::idl::{Idl, IdlInstruction, IdlInstructionAccountItem};
+
+/// Anchor's 8-byte instruction discriminator: the first 8 bytes of
+/// `sha256("global:<snake_case_instruction_name>")`. Every generated
+/// instruction's `data` starts with this, the same way anchor-client and
+/// the `#[program]` macro's dispatcher agree on it.
+fn sighash(instruction_name: &str) -> [u8; 8] {
+    let preimage = format!("global:{}", instruction_name.to_snake_case());
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&solana_sdk::hash::hash(preimage.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Generates `program_client/src/lib.rs`: a typed request-builder per
+/// instruction, following the `program.request().accounts(...).send()`
+/// shape anchor-client uses instead of a flat, positional free function.
+///
+/// Each `<Instruction>Builder` tracks, via const-generic flags, which
+/// required accounts have been set, so `.instruction()`/`.send()` are only
+/// callable once every required account is present — the ordering
+/// footgun of the old `log_message(&client, token, authority, [signers])`
+/// signature becomes a compile error instead of a runtime one.
+pub fn generate_source_code(idl: Idl) -> String {
+    let modules = idl.programs.into_iter().map(|program| {
+        let module_name = format_ident!("{}_instruction", program.name.to_snake_case());
+        let builders = program.instructions.iter().map(generate_instruction_builder);
+        quote! {
+            pub mod #module_name {
+                use super::*;
+
+                #(#builders)*
+            }
+        }
+    });
+
+    let code = quote! {
+        use solana_sdk::{
+            instruction::{AccountMeta, Instruction},
+            pubkey::Pubkey,
+            signature::{Keypair, Signer},
+        };
+        use trdelnik::{Client, ClientError};
+
+        #(#modules)*
+    };
+
+    code.to_string()
+}
+
+fn generate_instruction_builder(instruction: &IdlInstruction) -> TokenStream {
+    let builder_name = format_ident!("{}Builder", instruction.name.to_camel_case());
+    let args_name = format_ident!("{}Args", instruction.name.to_camel_case());
+    let has_args = !instruction.args.is_empty();
+    let discriminator = sighash(&instruction.name).iter().map(|byte| quote! { #byte });
+
+    let account_idents: Vec<Ident> = instruction
+        .accounts
+        .iter()
+        .map(|account| format_ident!("{}", account.name().to_snake_case()))
+        .collect();
+    let account_flags: Vec<Ident> = instruction
+        .accounts
+        .iter()
+        .map(|account| Ident::new(&account.name().to_shouty_snake_case(), Span::call_site()))
+        .collect();
+
+    // `args` is tracked as just another required field when the
+    // instruction takes any, so `.args(...)` participates in the same
+    // completeness check as the accounts. The builder always carries an
+    // `args` field so every setter can copy it unconditionally; its type is
+    // `()` (trivially "complete") when the instruction takes no arguments.
+    let args_flag = has_args.then(|| format_ident!("ARGS"));
+    let flags: Vec<Ident> = account_flags.iter().cloned().chain(args_flag.clone()).collect();
+    let fields = account_idents.iter().map(|ident| quote! { #ident: Option<Pubkey> });
+    let all_false = flags.iter().map(|_| quote! { false });
+    let all_true = flags.iter().map(|_| quote! { true });
+
+    let args_field_ty = if has_args { quote! { Option<#args_name> } } else { quote! { () } };
+    let args_field_init = if has_args { quote! { None } } else { quote! { () } };
+    let args_struct_fields = instruction.args.iter().map(|arg| {
+        let name = format_ident!("{}", arg.name.to_snake_case());
+        let ty = &arg.ty;
+        quote! { pub #name: #ty }
+    });
+    let args_struct = has_args.then(|| {
+        quote! {
+            #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+            pub struct #args_name {
+                #(#args_struct_fields,)*
+            }
+        }
+    });
+
+    // One `impl<const OTHER: bool, ...> Builder<..., false, ...>` block per
+    // required field (account or args), built directly rather than through
+    // a shared "reflag" helper: reusing the flags' own identifiers as a
+    // method-local generic parameter list shadows the enclosing impl's
+    // generics (rustc E0403), so the struct literal is constructed inline
+    // in each setter instead.
+    let account_setters = account_idents.iter().zip(&account_flags).enumerate().map(|(i, (field, _flag))| {
+        generate_setter(&builder_name, &flags, i, &account_idents, quote! { pubkey: Pubkey }, quote! { self.#field = Some(pubkey); }, field)
+    });
+
+    let args_setter = has_args.then(|| {
+        let i = flags.len() - 1;
+        generate_setter(&builder_name, &flags, i, &account_idents, quote! { args: #args_name }, quote! { self.args = Some(args); }, &format_ident!("args"))
+    });
+
+    let account_metas = instruction.accounts.iter().zip(&account_idents).map(|(account, field)| {
+        if account.is_signer() {
+            quote! { AccountMeta::new(self.#field.unwrap(), true) }
+        } else if account.is_mut() {
+            quote! { AccountMeta::new(self.#field.unwrap(), false) }
+        } else {
+            quote! { AccountMeta::new_readonly(self.#field.unwrap(), false) }
+        }
+    });
+
+    let data = if has_args {
+        quote! {
+            let mut data = vec![#(#discriminator),*];
+            borsh::BorshSerialize::serialize(self.args.as_ref().unwrap(), &mut data).unwrap();
+            data
+        }
+    } else {
+        quote! { vec![#(#discriminator),*] }
+    };
+
+    quote! {
+        #args_struct
+
+        pub struct #builder_name<#(const #flags: bool = false),*> {
+            program_id: Pubkey,
+            #(#fields,)*
+            args: #args_field_ty,
+            signers: Vec<Keypair>,
+        }
+
+        impl #builder_name<#(#all_false),*> {
+            pub fn new(program_id: Pubkey) -> Self {
+                Self {
+                    program_id,
+                    #(#account_idents: None,)*
+                    args: #args_field_init,
+                    signers: Vec::new(),
+                }
+            }
+        }
+
+        impl<#(const #flags: bool),*> #builder_name<#(#flags),*> {
+            /// Adds a transaction signer, e.g. the keypair of an account
+            /// above that must sign (the account setters take only its
+            /// `Pubkey`), mirroring anchor-client's `.signer(...)`.
+            pub fn signer(mut self, signer: Keypair) -> Self {
+                self.signers.push(signer);
+                self
+            }
+        }
+
+        #(#account_setters)*
+        #args_setter
+
+        impl #builder_name<#(#all_true),*> {
+            /// Returns the raw `Instruction`, uncallable until every
+            /// required account (and `.args(...)`, if the instruction takes
+            /// any) has been set, so it can be composed into a
+            /// multi-instruction `Transaction` or a CPI-style invocation.
+            pub fn instruction(&self) -> Instruction {
+                Instruction {
+                    program_id: self.program_id,
+                    accounts: vec![#(#account_metas),*],
+                    data: { #data },
+                }
+            }
+
+            pub async fn send(&self, client: &Client) -> Result<solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta, ClientError> {
+                client.send_instruction(self.instruction(), &self.signers).await
+            }
+        }
+    }
+}
+
+/// Builds one `impl<const OTHER: bool, ...> Builder<..., false, ...> { fn #setter_name(...) -> Builder<..., true, ...> }`
+/// block for the field at `flag_index`, constructing the struct literal
+/// directly rather than delegating to a shared "reflag" helper (see the
+/// comment above its call site — that helper's own generics would shadow
+/// the enclosing impl's).
+fn generate_setter(
+    builder_name: &Ident,
+    flags: &[Ident],
+    flag_index: usize,
+    account_idents: &[Ident],
+    param: TokenStream,
+    assign: TokenStream,
+    setter_name: &Ident,
+) -> TokenStream {
+    let other_flags: Vec<Ident> = flags
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != flag_index)
+        .map(|(_, f)| f.clone())
+        .collect();
+    let before_flags = flags.iter().enumerate().map(|(j, f)| if j == flag_index { quote! { false } } else { quote! { #f } });
+    let after_flags = flags.iter().enumerate().map(|(j, f)| if j == flag_index { quote! { true } } else { quote! { #f } });
+
+    quote! {
+        impl<#(const #other_flags: bool),*> #builder_name<#(#before_flags),*> {
+            pub fn #setter_name(mut self, #param) -> #builder_name<#(#after_flags),*> {
+                #assign
+                #builder_name {
+                    program_id: self.program_id,
+                    #(#account_idents: self.#account_idents,)*
+                    args: self.args,
+                    signers: self.signers,
+                }
+            }
+        }
+    }
+}
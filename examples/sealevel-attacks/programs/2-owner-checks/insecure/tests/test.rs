@@ -1,11 +1,11 @@
 use trdelnik::*;
 use fehler::throws;
 use program_client::owner_checks_insecure_instruction;
-use std::mem;
 use spl_token::state::{Account as TokenAccount, AccountState};
 use anchor_lang::solana_program::program_option::COption;
 use anchor_lang::solana_program::program_pack::Pack;
 use anchor_spl::token::TokenAccount as AnchorTokenAccount;
+use solana_sdk::system_program;
 
 #[trdelnik_test]
 async fn test_insecure() {
@@ -19,26 +19,29 @@ async fn test_insecure() {
         token_account: keypair(4),
         attacker: keypair(5),
     };
-    // deploy a tested program
-    fixture.deploy().await?;
-    // create a token account belonging to the `authority`
-    fixture.create_mock_token_account().await?;
+    // preload the tested program and the attacker-owned mock token account
+    // into the validator's genesis, instead of deploying/airdropping after
+    // it's already up.
+    let localnet = fixture.start_localnet().await?;
 
     let acc = fixture.client.account_data::<AnchorTokenAccount>(fixture.token_account.pubkey()).await?;
-    println!("Token account {:?} succesfully created\n\tamount: {:?}\n\towner: {:?}", 
-        fixture.token_account.pubkey(), 
-        acc.amount, 
+    println!("Token account {:?} succesfully created\n\tamount: {:?}\n\towner: {:?}",
+        fixture.token_account.pubkey(),
+        acc.amount,
         acc.owner
     );
     // call an intstruction
-    owner_checks_insecure_instruction::log_message(
-        &fixture.client,
-        fixture.token_account.pubkey(),
-        fixture.attacker.pubkey(),
-        [fixture.attacker]
-    ).await?.print();
+    owner_checks_insecure_instruction::LogMessageBuilder::new(fixture.program.pubkey())
+        .token(fixture.token_account.pubkey())
+        .authority(fixture.attacker.pubkey())
+        .signer(fixture.attacker)
+        .send(&fixture.client)
+        .await?
+        .print();
 
     println!("{:?}", program_keypair(3).pubkey());
+
+    localnet.stop().await?;
 }
 
 struct Fixture {
@@ -51,34 +54,45 @@ struct Fixture {
 }
 
 impl Fixture {
+    /// Declares the tested program, the attacker keypair and the mock
+    /// token account entirely up front, then boots the validator with all
+    /// of it already in genesis.
     #[throws]
-    async fn deploy(&mut self) {
-        self.client.airdrop(self.client.payer().pubkey(), 5_000_000_000).await?;
-        self.client.deploy(
-            self.program.clone(),
-            mem::take(&mut self.program_data)
-        ).await?;
+    async fn start_localnet(&self) -> LocalnetHandle {
+        let mut genesis = GenesisBuilder::new();
+        genesis.add_program(self.program.pubkey(), self.program_data.clone());
+        genesis.add_account(
+            self.token_account.pubkey(),
+            5_000_000_000,
+            spl_token::id(),
+            self.mock_token_account_data(),
+        );
+        // Fund the client's own payer too, since genesis doesn't auto-fund
+        // arbitrary keypairs and it's the fee payer for the instruction call.
+        genesis.add_account(
+            self.client.payer().pubkey(),
+            5_000_000_000,
+            system_program::id(),
+            vec![],
+        );
+
+        Commander::new().start_localnet_with_genesis(Some(&genesis)).await?
     }
-    #[throws]
-    async fn create_mock_token_account(&self) {
-        let token_account = 
-            TokenAccount {
-                mint: Pubkey::default(),
-                owner: self.attacker.pubkey(),
-                amount: u64::MAX,
-                delegate: COption::None,
-                state: AccountState::Initialized,
-                is_native: COption::None,
-                delegated_amount: 0,
-                close_authority: COption::None,
-            };
+
+    fn mock_token_account_data(&self) -> Vec<u8> {
+        let token_account = TokenAccount {
+            mint: Pubkey::default(),
+            owner: self.attacker.pubkey(),
+            amount: u64::MAX,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
 
         let mut buf = [0; TokenAccount::LEN];
         token_account.pack_into_slice(&mut buf);
-        
-        self.client.create_account_with_data(
-            &self.token_account,
-            buf.to_vec()
-        ).await?;
+        buf.to_vec()
     }
-}
\ No newline at end of file
+}